@@ -1,44 +1,260 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use std::fs;
-use std::path::PathBuf;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
 use std::time::Instant;
 
 use clap::Parser;
 
+/// The default English digit vocabulary used when no `--vocab` file is given.
 const FIGURES: [(&str, u8); 9] = [
     ("one", 1), ("two", 2), ("three", 3), ("four", 4),
     ("five", 5), ("six", 6), ("seven", 7), ("eight", 8), ("nine", 9)
 ];
-const R_FIGURES: [(&str, u8); 9] = [
-    ("eno", 1), ("owt", 2), ("eerht", 3), ("ruof", 4),
-    ("evif", 5), ("xis", 6), ("neves", 7), ("thgie", 8), ("enin", 9)
-];
 
-struct Node {
-    leaf: Option<u8>,
-    children: HashMap<char, Node>,
+/// A single node of the Aho-Corasick automaton.
+///
+/// The payload `V` is whatever value a word maps to; for the calibration
+/// puzzle it is the digit (`u8`), but the node itself is agnostic. Children
+/// are stored as arena indices into [`Automaton::nodes`] so that the failure
+/// links (which point at arbitrary other nodes) can be represented without
+/// fighting the borrow checker.
+struct Node<V> {
+    leaf: Option<V>,
+    depth: usize,
+    children: HashMap<char, usize>,
+    failure: usize,
+    /// Every match ending at this node, as `(value, word_length)` pairs: the
+    /// node's own leaf unioned with the output of its failure target.
+    output: Vec<(V, usize)>,
 }
 
-impl Node {
-    fn new(leaf: Option<u8>) -> Node {
+impl<V> Node<V> {
+    fn new(depth: usize) -> Node<V> {
         Node {
-            leaf,
+            leaf: None,
+            depth,
             children: HashMap::new(),
+            failure: 0,
+            output: vec![],
+        }
+    }
+}
+
+/// Aho-Corasick automaton over a digit vocabulary.
+///
+/// A single left-to-right scan yields every spelled-out (and literal) digit in
+/// a line, so overlaps like `"eightwo"` or `"oneight"` fall out naturally
+/// without the old reverse pass.
+struct Automaton<V> {
+    nodes: Vec<Node<V>>,
+}
+
+impl<V: Clone> Automaton<V> {
+    /// Builds the goto trie and then wires up the failure links by BFS.
+    fn build<'a>(figures: impl IntoIterator<Item = (&'a str, V)>) -> Automaton<V> {
+        let mut nodes = vec![Node::new(0)];
+        for (fig, val) in figures {
+            let mut cur = 0;
+            for c in fig.chars() {
+                cur = match nodes[cur].children.get(&c).copied() {
+                    Some(next) => next,
+                    None => {
+                        let depth = nodes[cur].depth + 1;
+                        nodes.push(Node::new(depth));
+                        let idx = nodes.len() - 1;
+                        nodes[cur].children.insert(c, idx);
+                        idx
+                    }
+                };
+            }
+            nodes[cur].leaf = Some(val);
+        }
+
+        let mut automaton = Automaton { nodes };
+        automaton.build_failure_links();
+        automaton
+    }
+
+    /// Computes failure links in breadth-first order. The root's direct
+    /// children fail to the root; every other node's failure is found by
+    /// following its parent's failure chain until some ancestor has a child on
+    /// the edge character (or the root is reached).
+    fn build_failure_links(&mut self) {
+        let mut queue: VecDeque<usize> = VecDeque::new();
+        let root_children: Vec<usize> = self.nodes[0].children.values().copied().collect();
+        for child in root_children {
+            self.nodes[child].failure = 0;
+            if let Some(val) = self.nodes[child].leaf.clone() {
+                self.nodes[child].output = vec![(val, self.nodes[child].depth)];
+            }
+            queue.push_back(child);
+        }
+
+        while let Some(u) = queue.pop_front() {
+            let edges: Vec<(char, usize)> = self.nodes[u]
+                .children
+                .iter()
+                .map(|(c, &n)| (*c, n))
+                .collect();
+            for (c, v) in edges {
+                let mut f = self.nodes[u].failure;
+                while f != 0 && !self.nodes[f].children.contains_key(&c) {
+                    f = self.nodes[f].failure;
+                }
+                let failure = match self.nodes[f].children.get(&c).copied() {
+                    Some(next) if next != v => next,
+                    _ => 0,
+                };
+                self.nodes[v].failure = failure;
+
+                // output set = own leaf unioned with the failure target's output
+                let mut output = self.nodes[failure].output.clone();
+                if let Some(val) = self.nodes[v].leaf.clone() {
+                    output.push((val, self.nodes[v].depth));
+                }
+                self.nodes[v].output = output;
+
+                queue.push_back(v);
+            }
         }
     }
 
-    /// build trie from figure
-    fn add_path(figure: &str, value: u8, node: &mut Node) {
-        let mut child = node;
-        for c in figure.chars() {
-            child = child.add_child(c, None);
+    /// Follows the goto edge on `c`, falling back along the failure chain on a
+    /// mismatch, and returns the resulting state.
+    fn step(&self, mut state: usize, c: char) -> usize {
+        loop {
+            if let Some(&next) = self.nodes[state].children.get(&c) {
+                return next;
+            }
+            if state == 0 {
+                return 0;
+            }
+            state = self.nodes[state].failure;
         }
-        child.leaf = Some(value);
     }
+}
 
-    /// Adds child or returns existing child.
-    fn add_child(&mut self, key: char, leaf: Option<u8>) -> &mut Node {
-        self.children.entry(key).or_insert_with(|| Node::new(leaf))
+/// A matched digit paired with the 0-based character column it started at, or
+/// `None` when the line yielded no digit.
+type DigitHit = Option<(u8, usize)>;
+
+impl Automaton<u8> {
+    /// Scans the line once, returning the first and last digit as
+    /// `(value, start_column)` pairs (0-based character columns). A literal
+    /// ASCII digit is preferred over a spelled-out match at its position.
+    fn scan(&self, line: &str) -> (DigitHit, DigitHit) {
+        let mut state = 0;
+        let mut first = None;
+        let mut last = None;
+        let mut record = |m: (u8, usize)| {
+            if first.is_none() {
+                first = Some(m);
+            }
+            last = Some(m);
+        };
+
+        for (i, c) in line.chars().enumerate() {
+            if let Some(d) = c.to_digit(10) {
+                // a literal digit breaks any partial word match
+                state = 0;
+                record((d as u8, i));
+                continue;
+            }
+            state = self.step(state, c);
+            // several vocabulary words can end at this position when they share
+            // a suffix; the longest one starts leftmost, which is the match we
+            // want to credit as the first/last digit here.
+            if let Some(&(val, len)) = self.nodes[state].output.iter().max_by_key(|&&(_, len)| len) {
+                record((val, i + 1 - len));
+            }
+        }
+
+        (first, last)
+    }
+}
+
+/// A runtime-configurable mapping from spelled-out words to digit values,
+/// together with the radix used to combine the first and last digit.
+///
+/// Defaults to English base-10, but an external file (`--vocab`) and
+/// `--radix` let the crate act as a general first/last-token extractor for
+/// other languages or bases.
+struct Vocabulary {
+    figures: Vec<(String, u8)>,
+    radix: u32,
+}
+
+impl Vocabulary {
+    /// The built-in English base-10 vocabulary.
+    fn english() -> Vocabulary {
+        Vocabulary {
+            figures: FIGURES.iter().map(|(w, v)| (w.to_string(), *v)).collect(),
+            radix: 10,
+        }
+    }
+
+    /// Loads a vocabulary from a file of `word=value` lines. Blank lines and
+    /// lines starting with `#` are ignored. The radix defaults to 10 and can
+    /// be overridden afterwards.
+    fn load(path: &Path) -> io::Result<Vocabulary> {
+        let contents = fs::read_to_string(path)?;
+        let mut figures = vec![];
+        for line in contents.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (word, value) = line.split_once('=').ok_or_else(|| {
+                io::Error::new(io::ErrorKind::InvalidData, format!("invalid vocabulary entry: {line}"))
+            })?;
+            let value: u8 = value.trim().parse().map_err(|_| {
+                io::Error::new(io::ErrorKind::InvalidData, format!("invalid digit value: {}", value.trim()))
+            })?;
+            figures.push((word.trim().to_string(), value));
+        }
+        Ok(Vocabulary { figures, radix: 10 })
+    }
+
+    fn automaton(&self) -> Automaton<u8> {
+        Automaton::build(self.figures.iter().map(|(w, v)| (w.as_str(), *v)))
+    }
+}
+
+/// Where a digit was recognised within a line.
+///
+/// Columns are 0-based character offsets and point at the first character of
+/// the spelled-out (or literal) digit. `col_first`/`col_last` are `None` when
+/// the line yielded no digit at all.
+struct Span {
+    line: usize,
+    col_first: Option<usize>,
+    col_last: Option<usize>,
+}
+
+/// Source-site information gathered while parsing a whole document.
+struct Report {
+    spans: Vec<Span>,
+}
+
+impl Report {
+    fn new() -> Report {
+        Report { spans: vec![] }
+    }
+
+    /// Prints every line the parser ignored (no digit found) so users see
+    /// exactly which input lines were dropped. Successful spans are still
+    /// recorded in the report, just not printed.
+    fn render(&self, cal_doc: &str) {
+        let lines: Vec<&str> = cal_doc.lines().collect();
+        for span in &self.spans {
+            if span.col_first.is_some() {
+                continue;
+            }
+            let text = lines.get(span.line - 1).copied().unwrap_or("");
+            println!("line {}: no digit found", span.line);
+            println!("  {}", text);
+        }
     }
 }
 
@@ -48,6 +264,26 @@ struct Cli {
     /// Sets a custom config file
     #[arg(short, long, required = true, value_name = "FILE")]
     cal_doc: Option<PathBuf>,
+
+    /// Report every line the parser could not read a digit from
+    #[arg(long)]
+    diagnostics: bool,
+
+    /// Load the word-to-digit vocabulary from a `word=value` file
+    #[arg(long, value_name = "FILE")]
+    vocab: Option<PathBuf>,
+
+    /// Radix used when combining the first and last digit
+    #[arg(long, default_value_t = 10)]
+    radix: u32,
+
+    /// Puzzle part: 1 matches ASCII digits only, 2 also matches spelled-out words
+    #[arg(long, default_value_t = 2, value_parser = clap::value_parser!(u8).range(1..=2))]
+    part: u8,
+
+    /// Benchmark the parser over N iterations instead of printing a single timing
+    #[arg(long, value_name = "ITERS")]
+    bench: Option<u32>,
 }
 
 fn main() {
@@ -55,61 +291,185 @@ fn main() {
 
     let config_path = cli.cal_doc.as_deref().expect("cal_doc is required");
 
+    let mut vocab = match cli.vocab.as_deref() {
+        Some(path) => Vocabulary::load(path).expect("failed to read vocab"),
+        None => Vocabulary::english(),
+    };
+    vocab.radix = cli.radix;
+
+    // part 1 only reads ASCII digits, so drop the spelled-out vocabulary
+    if cli.part == 1 {
+        vocab.figures.clear();
+    }
+
+    // "-" streams the document from stdin one line at a time, so the diagnostics
+    // and benchmarking features (which need the whole document in memory) are
+    // skipped in favour of a bounded-memory pass that still honours the selected
+    // vocabulary, radix and part.
+    if config_path == Path::new("-") {
+        let result = parse_cal_doc_stream(io::stdin().lock(), &vocab).expect("failed to read stdin");
+        println!("sum of calibration values: {}", result);
+        return;
+    }
+
     let cal_doc = fs::read_to_string(config_path).expect("failed to read cal_doc");
 
+    if let Some(iters) = cli.bench {
+        run_bench(&cal_doc, &vocab, iters);
+        return;
+    }
+
     let t0 = Instant::now();
-    let result = parse_cal_doc(&cal_doc);
+    let (result, report) = parse_cal_doc_report(&cal_doc, &vocab);
     let t1 = Instant::now();
     let elapsed_time = t1 - t0;
 
     println!("sum of calibration values: {}", result);
-    println!("took: {} Âµs", elapsed_time.as_micros())
+    println!("took: {} Âµs", elapsed_time.as_micros());
+
+    if cli.diagnostics {
+        report.render(&cal_doc);
+    }
 }
 
-fn parse_cal_doc(cal_doc: &str) -> u32 {
-    let mut tree = Node::new(None);
-    FIGURES.iter().for_each(|(fig, val)| Node::add_path(fig, *val, &mut tree));
+/// Runs [`parse_cal_doc_report`] `iters` times, discards a tenth of the runs as
+/// warmup, and reports the min/median/mean/p99 of the per-run timings so that
+/// regressions in the automaton scan are visible as a distribution rather than
+/// a single noisy sample.
+fn run_bench(cal_doc: &str, vocab: &Vocabulary, iters: u32) {
+    let warmup = iters / 10;
+    let mut samples: Vec<u128> = Vec::with_capacity(iters.saturating_sub(warmup) as usize);
+    for i in 0..iters {
+        let t0 = Instant::now();
+        let result = parse_cal_doc_report(cal_doc, vocab);
+        let elapsed = t0.elapsed().as_micros();
+        // keep the optimiser from discarding the work
+        std::hint::black_box(result);
+        if i >= warmup {
+            samples.push(elapsed);
+        }
+    }
 
-    let mut rtree = Node::new(None);
-    R_FIGURES.iter().for_each(|(fig, val)| Node::add_path(fig, *val, &mut rtree));
+    if samples.is_empty() {
+        println!("no samples collected (iters too small)");
+        return;
+    }
 
-    cal_doc.lines().map(|line| parse_cal_doc_line(line, &tree, &rtree)).sum()
+    samples.sort_unstable();
+    let n = samples.len();
+    let min = samples[0];
+    let median = samples[n / 2];
+    let mean = samples.iter().sum::<u128>() / n as u128;
+    let p99 = samples[(n - 1) * 99 / 100];
+
+    println!(
+        "bench over {} runs ({} warmup): min {} µs, median {} µs, mean {} µs, p99 {} µs",
+        n, warmup, min, median, mean, p99
+    );
 }
 
-fn parse_cal_doc_line(cal_doc_line: &str, tree: &Node, rtree: &Node) -> u32 {
-    let first = find_digit(cal_doc_line.chars(), tree);
-    if first.is_none() { return 0 }
-    let last = find_digit(cal_doc_line.chars().rev(), rtree);
+#[cfg(test)]
+fn parse_cal_doc(cal_doc: &str) -> u32 {
+    parse_cal_doc_report(cal_doc, &Vocabulary::english()).0
+}
 
-    first.unwrap_or(0) as u32 * 10 + last.unwrap_or(0) as u32
+/// Parses the document and, alongside the summed total, collects a [`Report`]
+/// recording where each line's first and last digit were found.
+fn parse_cal_doc_report(cal_doc: &str, vocab: &Vocabulary) -> (u32, Report) {
+    let automaton = vocab.automaton();
+
+    let mut report = Report::new();
+    let mut sum = 0u32;
+    for (i, line) in cal_doc.lines().enumerate() {
+        let (first, last) = automaton.scan(line);
+        let span = match first {
+            None => Span { line: i + 1, col_first: None, col_last: None },
+            Some((first_val, first_col)) => {
+                let (last_val, last_col) = last.unwrap_or((first_val, first_col));
+                sum += first_val as u32 * vocab.radix + last_val as u32;
+                Span { line: i + 1, col_first: Some(first_col), col_last: Some(last_col) }
+            }
+        };
+        report.spans.push(span);
+    }
+
+    (sum, report)
 }
 
-fn find_digit(cal_doc_line_chars: impl Iterator<Item=char>, tree: &Node) -> Option<u8> {
-    let mut nodes: Vec<&Node> = vec![];
-    for c in cal_doc_line_chars {
-        if c.is_numeric() {
-            return Some(c.to_digit(10).unwrap_or(0) as u8);
+/// Streams a calibration document from any reader, decoding bytes into `char`s
+/// incrementally and feeding completed lines to [`parse_cal_doc_line`] so that
+/// memory stays bounded regardless of input size. A codepoint split across two
+/// read boundaries is buffered until the rest of its bytes arrive; invalid
+/// bytes are skipped rather than aborting the parse. The `vocab` (and its
+/// radix) drives the match, so `--vocab`/`--radix`/`--part` are honoured on the
+/// streaming path just as on the in-memory one.
+fn parse_cal_doc_stream<R: Read>(mut reader: R, vocab: &Vocabulary) -> io::Result<u32> {
+    let automaton = vocab.automaton();
+
+    let mut sum = 0u32;
+    let mut line = String::new();
+    let mut carry: Vec<u8> = Vec::new();
+    let mut buf = [0u8; 8192];
+
+    loop {
+        let n = reader.read(&mut buf)?;
+        if n == 0 {
+            break;
         }
-        let mut new_nodes = vec![];
-        for node in nodes {
-            if node.children.contains_key(&c) {
-                let new_node = node.children.get(&c).unwrap();
-                if new_node.leaf.is_some() {
-                    return new_node.leaf;
+        carry.extend_from_slice(&buf[..n]);
+
+        loop {
+            // decode the longest valid UTF-8 prefix currently buffered
+            let (valid, skip) = match std::str::from_utf8(&carry) {
+                Ok(s) => (s.len(), 0),
+                Err(e) => match e.error_len() {
+                    // incomplete trailing codepoint: keep it for the next read
+                    None => (e.valid_up_to(), 0),
+                    // genuinely invalid bytes: drop them and carry on
+                    Some(bad) => (e.valid_up_to(), bad),
+                },
+            };
+
+            if valid > 0 {
+                let text = std::str::from_utf8(&carry[..valid]).expect("valid prefix");
+                for c in text.chars() {
+                    if c == '\n' {
+                        // strip a trailing '\r' so CRLF input matches str::lines()
+                        if line.ends_with('\r') {
+                            line.pop();
+                        }
+                        sum += parse_cal_doc_line(&line, &automaton, vocab.radix);
+                        line.clear();
+                    } else {
+                        line.push(c);
+                    }
                 }
-                new_nodes.push(new_node)
             }
-        }
-        nodes = new_nodes;
 
-        // a new figure might be starting from this character on
-        if tree.children.contains_key(&c) {
-            let new_node = tree.children.get(&c).unwrap();
-            nodes.push(new_node);
+            carry.drain(..valid + skip);
+            if skip == 0 {
+                break;
+            }
         }
     }
 
-    None
+    // a final line without a trailing newline still counts
+    if !line.is_empty() {
+        sum += parse_cal_doc_line(&line, &automaton, vocab.radix);
+    }
+
+    Ok(sum)
+}
+
+fn parse_cal_doc_line(cal_doc_line: &str, automaton: &Automaton<u8>, radix: u32) -> u32 {
+    let (first, last) = automaton.scan(cal_doc_line);
+    match first {
+        None => 0,
+        Some((first_val, _)) => {
+            let last_val = last.map(|(v, _)| v).unwrap_or(first_val);
+            first_val as u32 * radix + last_val as u32
+        }
+    }
 }
 
 #[cfg(test)]
@@ -118,25 +478,27 @@ mod tests {
 
     #[test]
     fn parse_cal_doc_line_test() {
-        let result = parse_cal_doc_line("1abc2", &get_tree(), &get_rtree());
+        let automaton = get_automaton();
+
+        let result = parse_cal_doc_line("1abc2", &automaton, 10);
         assert_eq!(result, 12);
 
-        let result = parse_cal_doc_line("pqr3stu8vwx", &get_tree(), &get_rtree());
+        let result = parse_cal_doc_line("pqr3stu8vwx", &automaton, 10);
         assert_eq!(result, 38);
 
-        let result = parse_cal_doc_line("eightwothree", &get_tree(), &get_rtree());
+        let result = parse_cal_doc_line("eightwothree", &automaton, 10);
         assert_eq!(result, 83);
 
-        let result = parse_cal_doc_line("twoeighthree", &get_tree(), &get_rtree());
+        let result = parse_cal_doc_line("twoeighthree", &automaton, 10);
         assert_eq!(result, 23);
 
-        let result = parse_cal_doc_line("eightwothree", &get_tree(), &get_rtree());
+        let result = parse_cal_doc_line("eightwothree", &automaton, 10);
         assert_eq!(result, 83);
 
-        let result = parse_cal_doc_line("fifour", &get_tree(), &get_rtree());
+        let result = parse_cal_doc_line("fifour", &automaton, 10);
         assert_eq!(result, 44);
 
-        let result = parse_cal_doc_line("onine", &get_tree(), &get_rtree());
+        let result = parse_cal_doc_line("onine", &automaton, 10);
         assert_eq!(result, 99);
     }
 
@@ -146,6 +508,44 @@ mod tests {
         assert_eq!(result, 885);
     }
 
+    #[test]
+    fn report_flags_lines_without_digits() {
+        let (_, report) = parse_cal_doc_report("one2three\nnodigitshere\n", &Vocabulary::english());
+        assert_eq!(report.spans.len(), 2);
+        assert_eq!(report.spans[0].col_first, Some(0));
+        assert_eq!(report.spans[0].col_last, Some(4));
+        assert_eq!(report.spans[1].col_first, None);
+        assert_eq!(report.spans[1].col_last, None);
+    }
+
+    #[test]
+    fn custom_vocabulary_with_arbitrary_radix() {
+        let figures = [("ten", 10u8), ("eleven", 11)];
+        let automaton = Automaton::build(figures.iter().map(|(w, v)| (*w, *v)));
+
+        // first = "ten" (10), last = literal "3"; combined in base 16
+        let result = parse_cal_doc_line("ten3", &automaton, 16);
+        assert_eq!(result, 10 * 16 + 3);
+    }
+
+    #[test]
+    fn suffix_sharing_vocabulary_prefers_leftmost_match() {
+        // "she" and "he" both end at the same column in "ushers"; the first
+        // digit must be the leftmost-starting "she", not the shorter "he".
+        let figures = [("he", 1u8), ("she", 2), ("hers", 4)];
+        let automaton = Automaton::build(figures.iter().map(|(w, v)| (*w, *v)));
+
+        assert_eq!(parse_cal_doc_line("ushers", &automaton, 10), 24);
+    }
+
+    #[test]
+    fn part_one_ignores_spelled_out_words() {
+        // an empty vocabulary is the part-1 rule: ASCII digits only
+        let automaton = Automaton::build(std::iter::empty::<(&str, u8)>());
+        assert_eq!(parse_cal_doc_line("one2three4", &automaton, 10), 24);
+        assert_eq!(parse_cal_doc_line("eightwothree", &automaton, 10), 0);
+    }
+
     fn cal_doc_fixture() -> String {
         String::from(
             "two1nine\n\
@@ -171,22 +571,58 @@ mod tests {
     }
 
     #[test]
-    fn find_digit_test() {
-        let val = find_digit("oneabs".chars(), &get_tree());
+    fn scan_finds_first_and_last() {
+        let automaton = get_automaton();
+        let (first, last) = automaton.scan("oneabs");
+
+        assert_eq!(first, Some((1, 0)));
+        assert_eq!(last, Some((1, 0)));
+    }
 
-        assert!(val.is_some());
-        assert_eq!(1, val.unwrap())
+    fn get_automaton() -> Automaton<u8> {
+        Vocabulary::english().automaton()
     }
 
-    fn get_tree() -> Node {
-        let mut tree = Node::new(None);
-        FIGURES.iter().for_each(|(fig, val)| Node::add_path(fig, *val, &mut tree));
-        tree
+    /// A reader that hands out at most `chunk` bytes per `read`, so tests can
+    /// force multi-byte codepoints to straddle read boundaries.
+    struct ChunkReader<'a> {
+        data: &'a [u8],
+        pos: usize,
+        chunk: usize,
     }
 
-    fn get_rtree() -> Node {
-        let mut rtree = Node::new(None);
-        R_FIGURES.iter().for_each(|(fig, val)| Node::add_path(fig, *val, &mut rtree));
-        rtree
+    impl<'a> Read for ChunkReader<'a> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let remaining = &self.data[self.pos..];
+            let n = remaining.len().min(self.chunk).min(buf.len());
+            buf[..n].copy_from_slice(&remaining[..n]);
+            self.pos += n;
+            Ok(n)
+        }
+    }
+
+    #[test]
+    fn parse_cal_doc_stream_test() {
+        let data = "two1nine\neightwothree\n".as_bytes();
+        let reader = ChunkReader { data, pos: 0, chunk: 1 };
+        assert_eq!(parse_cal_doc_stream(reader, &Vocabulary::english()).unwrap(), 29 + 83);
+    }
+
+    #[test]
+    fn parse_cal_doc_stream_handles_split_multibyte() {
+        // "é" is two bytes; a one-byte chunk size splits it across reads. The
+        // trailing line has no newline, so it must still be counted.
+        let data = "abéc9\nsé7en".as_bytes();
+        let reader = ChunkReader { data, pos: 0, chunk: 1 };
+        assert_eq!(parse_cal_doc_stream(reader, &Vocabulary::english()).unwrap(), 99 + 77);
+    }
+
+    #[test]
+    fn parse_cal_doc_stream_matches_in_memory_on_crlf() {
+        // CRLF input must agree with the str::lines()-based in-memory path
+        let doc = "two1nine\r\neightwothree\r\n";
+        let reader = ChunkReader { data: doc.as_bytes(), pos: 0, chunk: 1 };
+        let streamed = parse_cal_doc_stream(reader, &Vocabulary::english()).unwrap();
+        assert_eq!(streamed, parse_cal_doc(doc));
     }
 }